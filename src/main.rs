@@ -5,21 +5,35 @@ extern crate lazy_static;
 extern crate anyhow;
 
 use chrono::prelude::*;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
 use itertools::Itertools;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::convert::Infallible;
+use std::convert::TryFrom;
 use std::fmt;
+use std::fmt::Write as _;
 use std::fs::File;
 use std::fs::OpenOptions;
 use std::io::BufRead;
 use std::io::BufReader;
+use std::io::BufWriter;
 use std::io::Write;
+use std::net::SocketAddr;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use structopt::clap::arg_enum;
 use structopt::StructOpt;
 
 use std::env;
 use telegram_bot::*;
 use tokio::stream::StreamExt;
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::time::delay_for;
 
 use anyhow::{Context, Result};
 
@@ -33,6 +47,14 @@ enum Opt {
 
         #[structopt(short, long, index = 2)]
         info: String,
+
+        #[structopt(
+            long,
+            allow_hyphen_values = true,
+            parse(try_from_str = parse_at),
+            help = "Backdate the entry: an hour (9), a relative offset (-2d, yesterday), or an RFC3339 timestamp."
+        )]
+        at: Option<DateTime<Local>>,
     },
 
     #[structopt(help = "Query the track file.")]
@@ -40,12 +62,142 @@ enum Opt {
         #[structopt(short, long, index = 1)]
         category: String,
 
-        #[structopt(short, long, index = 2, default_value = "7")]
-        range: i64,
+        #[structopt(
+            short,
+            long,
+            index = 2,
+            default_value = "7",
+            help = "A day count (7) or a `from..to` date range."
+        )]
+        range: QueryRange,
+    },
+
+    #[structopt(help = "Show aggregate statistics for a category.")]
+    Stats {
+        #[structopt(short, long, index = 1)]
+        category: String,
+
+        #[structopt(short, long, index = 2)]
+        range: Option<i64>,
     },
 
     #[structopt(help = "Start telegram bot.")]
     Bot {},
+
+    #[structopt(help = "Export entries to a file.")]
+    Export {
+        #[structopt(
+            long,
+            possible_values = &Format::variants(),
+            case_insensitive = true,
+            default_value = "Json"
+        )]
+        format: Format,
+
+        #[structopt(short, long, parse(from_os_str))]
+        out: PathBuf,
+    },
+
+    #[structopt(help = "Import entries from a file.")]
+    Import {
+        #[structopt(
+            long,
+            possible_values = &Format::variants(),
+            case_insensitive = true,
+            default_value = "Json"
+        )]
+        format: Format,
+
+        #[structopt(short, long, parse(from_os_str))]
+        file: PathBuf,
+    },
+
+    #[structopt(help = "Serve add/query over a small HTTP+JSON API.")]
+    Serve {
+        #[structopt(long, default_value = "127.0.0.1:3030")]
+        addr: SocketAddr,
+    },
+
+    #[structopt(help = "Post a daily summary to the webhooks in TRACK_WEBHOOK_URLS.")]
+    Notify {
+        #[structopt(long, help = "Print the payload instead of sending it.")]
+        dry_run: bool,
+    },
+}
+
+arg_enum! {
+    #[derive(Debug, Clone, Copy)]
+    enum Format {
+        Plain,
+        Json,
+        Csv,
+        Msgpack,
+    }
+}
+
+/// A day count counted back from today, or an explicit `from..to` window.
+#[derive(Debug, Clone)]
+enum QueryRange {
+    Days(i64),
+    Window {
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+    },
+}
+
+impl std::str::FromStr for QueryRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<QueryRange> {
+        if let Some(idx) = s.find("..") {
+            let from = parse_at(&s[..idx])?;
+            let to = parse_at(&s[idx + 2..])?;
+            return Ok(QueryRange::Window { from, to });
+        }
+
+        let days = s
+            .parse::<i64>()
+            .with_context(|| format!("Invalid range: '{}'", s))?;
+        Ok(QueryRange::Days(days))
+    }
+}
+
+/// Parses a bare hour, a relative offset (`-2d`, `yesterday`), or an RFC3339 timestamp.
+fn parse_at(s: &str) -> Result<DateTime<Local>> {
+    let s = s.trim();
+
+    if let Ok(hour) = s.parse::<u32>() {
+        if hour < 24 {
+            let mut at = Local::now().date().and_hms(hour, 0, 0);
+            if at > Local::now() {
+                at = at - chrono::Duration::days(1);
+            }
+            return Ok(at);
+        }
+    }
+
+    if s.eq_ignore_ascii_case("today") {
+        return Ok(Local::now());
+    }
+    if s.eq_ignore_ascii_case("yesterday") {
+        return Ok(Local::now() - chrono::Duration::days(1));
+    }
+
+    lazy_static! {
+        static ref RELATIVE_RE: Regex = Regex::new(r"(?i)^-(\d+)([dh])$").unwrap();
+    }
+    if let Some(caps) = RELATIVE_RE.captures(s) {
+        let amount: i64 = caps.get(1).unwrap().as_str().parse()?;
+        let offset = match caps.get(2).unwrap().as_str() {
+            "d" | "D" => chrono::Duration::days(amount),
+            _ => chrono::Duration::hours(amount),
+        };
+        return Ok(Local::now() - offset);
+    }
+
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Local))
+        .with_context(|| format!("Could not parse date/time: '{}'", s))
 }
 
 async fn run() -> Result<()> {
@@ -56,16 +208,35 @@ async fn run() -> Result<()> {
     let opt = Opt::from_args();
 
     match opt {
-        Opt::Add { category, info } => {
-            track.add_entry(&category, &info)?;
+        Opt::Add { category, info, at } => {
+            track.add_entry(&category, &info, at)?;
         }
         Opt::Query { category, range } => {
             track.load()?;
-            track.query(&category, range)?;
+            track.query(&category, &range)?;
+        }
+        Opt::Stats { category, range } => {
+            track.load()?;
+            track.stats(&category, range)?;
         }
         Opt::Bot {} => {
             track.telegram_bot().await?;
         }
+        Opt::Export { format, out } => {
+            track.load()?;
+            track.export(format, &out)?;
+        }
+        Opt::Import { format, file } => {
+            track.load()?;
+            track.import(format, &file)?;
+        }
+        Opt::Serve { addr } => {
+            track.load()?;
+            track.serve(addr).await?;
+        }
+        Opt::Notify { dry_run } => {
+            track.notify(dry_run).await?;
+        }
     };
 
     Ok(())
@@ -119,18 +290,31 @@ impl Track {
         Ok(())
     }
 
-    fn query(&self, categories: &str, range: i64) -> Result<()> {
-        let now: Date<Local> = Local::now().date();
-        let min_date: Date<Local> = now
-            .checked_sub_signed(chrono::Duration::days(range))
-            .unwrap();
+    fn query(&self, categories: &str, range: &QueryRange) -> Result<()> {
+        print!("{}", self.query_to_string(categories, range));
+        Ok(())
+    }
+
+    fn query_to_string(&self, categories: &str, range: &QueryRange) -> String {
         let mut print_date: bool;
         let mut print_category: bool;
+        let mut out = String::new();
+
+        let in_range = |date: Date<Local>| match range {
+            QueryRange::Days(days) => {
+                let min_date = Local::now()
+                    .date()
+                    .checked_sub_signed(chrono::Duration::days(*days))
+                    .unwrap();
+                date > min_date
+            }
+            QueryRange::Window { from, to } => date >= from.date() && date <= to.date(),
+        };
 
         for (date, entries) in self
             .entries
             .iter()
-            .filter(|e| e.categories.contains(&categories) && e.date.date() > min_date)
+            .filter(|e| e.categories.contains(&categories) && in_range(e.date.date()))
             .group_by(|e| e.date.date())
             .into_iter()
         {
@@ -144,7 +328,8 @@ impl Track {
                 let entry_infos = cat_entries.collect::<Vec<&Entry>>();
                 let entry_info_agg: EntryInfoAggregate = Entry::aggregate(entry_infos);
                 for (log, count) in &entry_info_agg.logs {
-                    println!(
+                    writeln!(
+                        out,
                         "{0: <12} {1: <15} {2: <15}",
                         if print_date {
                             print_date = false;
@@ -167,10 +352,12 @@ impl Track {
                                 String::new()
                             }
                         )
-                    );
+                    )
+                    .unwrap();
                 }
                 for (unit, total) in &entry_info_agg.quantities {
-                    println!(
+                    writeln!(
+                        out,
                         "{0: <12} {1: <10} {2: <30}",
                         if print_date {
                             print_date = false;
@@ -185,15 +372,101 @@ impl Track {
                             String::new()
                         },
                         format!("{}{} ", total, unit)
-                    );
+                    )
+                    .unwrap();
                 }
             }
         }
+
+        out
+    }
+
+    fn stats(&self, category: &str, range: Option<i64>) -> Result<()> {
+        print!("{}", self.stats_to_string(category, range));
         Ok(())
     }
 
-    fn add_entry(&self, categories: &str, info: &str) -> Result<()> {
-        let local: DateTime<Local> = Local::now();
+    fn stats_to_string(&self, category: &str, range: Option<i64>) -> String {
+        let min_date: Option<Date<Local>> = range.map(|r| {
+            Local::now()
+                .date()
+                .checked_sub_signed(chrono::Duration::days(r))
+                .unwrap()
+        });
+
+        let entries: Vec<&Entry> = self
+            .entries
+            .iter()
+            .filter(|e| {
+                e.categories.contains(category) && min_date.map_or(true, |min| e.date.date() > min)
+            })
+            .collect();
+
+        let mut out = String::new();
+
+        if entries.is_empty() {
+            writeln!(out, "No entries found for category '{}'.", category).unwrap();
+            return out;
+        }
+
+        let agg = Entry::aggregate(entries.clone());
+
+        let mut units: Vec<&String> = agg.quantities.keys().collect();
+        units.sort();
+        for unit in units {
+            let values: Vec<(NaiveDate, f32)> = entries
+                .iter()
+                .filter_map(|e| match &e.info {
+                    EntryInfo::Q(q) if &q.unit == unit => {
+                        Some((e.date.date().naive_local(), q.quantity))
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            let count = values.len() as f32;
+            let sum: f32 = values.iter().map(|(_, v)| v).sum();
+            let mean = sum / count;
+            let min = values.iter().map(|(_, v)| *v).fold(f32::INFINITY, f32::min);
+            let max = values
+                .iter()
+                .map(|(_, v)| *v)
+                .fold(f32::NEG_INFINITY, f32::max);
+
+            writeln!(
+                out,
+                "{0: <10} sum={1:<10.2} mean={2:<10.2} min={3:<10.2} max={4:<10.2} 4wk avg={5:.2}",
+                unit,
+                sum,
+                mean,
+                min,
+                max,
+                weekly_moving_average(&values)
+            )
+            .unwrap();
+        }
+
+        if !agg.logs.is_empty() {
+            let mut freq: Vec<(&String, &i64)> = agg.logs.iter().collect();
+            freq.sort_by(|a, b| b.1.cmp(a.1));
+            writeln!(out, "Frequency:").unwrap();
+            for (log, count) in freq {
+                writeln!(out, "  {0: <30} {1}", log, count).unwrap();
+            }
+
+            let dates: std::collections::BTreeSet<NaiveDate> =
+                entries.iter().map(|e| e.date.date().naive_local()).collect();
+            let (current, longest) = compute_streaks(&dates);
+            writeln!(out, "Current streak: {} day(s)", current).unwrap();
+            writeln!(out, "Longest streak: {} day(s)", longest).unwrap();
+        }
+
+        out
+    }
+
+    fn add_entry(&self, categories: &str, info: &str, at: Option<DateTime<Local>>) -> Result<()> {
+        validate_entry_fields(categories, info)?;
+        let local: DateTime<Local> = at.unwrap_or_else(Local::now);
         let file = OpenOptions::new().append(true).open(&self.track_file)?;
         let entry = Entry {
             date: local,
@@ -205,51 +478,503 @@ impl Track {
         Ok(())
     }
 
-    async fn telegram_bot(&self) -> Result<()> {
+    async fn telegram_bot(&mut self) -> Result<()> {
         let token = env::var("TELEGRAM_BOT_TOKEN").with_context(|| "TELEGRAM_BOT_TOKEN not set")?;
         let api = Api::new(token);
 
-        // Fetch new updates via long poll method
-        let mut stream = api.stream();
-        while let Some(update) = stream.next().await {
-            // If the received update contains a new message...
-            let update = update?;
-            if let UpdateKind::Message(message) = update.kind {
-                if let MessageKind::Text { ref data, .. } = message.kind {
-                    let first_space = data.find(' ');
-                    let res = match first_space {
-                        Some(v) => {
-                            let category = &data[0..v];
-                            let value = &data[v..].trim();
-
-                            if category.is_empty() {
-                                Err(anyhow!("Invalid entry: category is empty"))
-                            } else if value.is_empty() {
-                                Err(anyhow!("Invalid entry: value is empty"))
-                            } else {
-                                self.add_entry(category, value)
-                                    .with_context(|| "Failed to add entry")
-                            }
-                        }
-                        None => Err(anyhow!("Invalid entry")),
-                    };
-
-                    match res {
-                        Ok(_) => api.send(message.text_reply("Saved!")).await?,
-                        Err(e) => {
-                            api.send(message.text_reply(format!("Errored! {}", e)))
-                                .await?
+        let prefix = env::var("TELEGRAM_COMMAND_PREFIX").unwrap_or_else(|_| "/".to_string());
+        let command_re = Regex::new(&format!(
+            r"(?i)^{}(query|stats)\s+(\S+)(?:\s+(\S+))?\s*$",
+            regex::escape(&prefix)
+        ))?;
+
+        let floor = Duration::from_secs(1);
+        let cap = Duration::from_secs(60);
+        let mut backoff = floor;
+
+        loop {
+            // Fetch new updates via long poll method
+            let mut stream = api.stream();
+            loop {
+                match stream.next().await {
+                    Some(Ok(update)) => {
+                        backoff = floor;
+                        self.handle_telegram_update(&api, &command_re, update).await;
+                    }
+                    Some(Err(e)) => {
+                        eprintln!(
+                            "Telegram stream errored: {}. Reconnecting in {:?}...",
+                            e, backoff
+                        );
+                        break;
+                    }
+                    None => {
+                        eprintln!("Telegram stream disconnected. Reconnecting in {:?}...", backoff);
+                        break;
+                    }
+                }
+            }
+
+            delay_for(backoff).await;
+            backoff = std::cmp::min(backoff * 2, cap);
+        }
+    }
+
+    async fn handle_telegram_update(&mut self, api: &Api, command_re: &Regex, update: Update) {
+        // If the received update contains a new message...
+        if let UpdateKind::Message(message) = update.kind {
+            if let MessageKind::Text { ref data, .. } = message.kind {
+                if let Some(caps) = command_re.captures(data) {
+                    let reply_text = self.handle_telegram_command(&caps);
+                    if let Err(e) = api.send(message.text_reply(reply_text)).await {
+                        eprintln!("Failed to send Telegram reply: {}", e);
+                    }
+                    return;
+                }
+
+                let first_space = data.find(' ');
+                let res = match first_space {
+                    Some(v) => {
+                        let category = &data[0..v];
+                        let value = &data[v..].trim();
+
+                        if category.is_empty() {
+                            Err(anyhow!("Invalid entry: category is empty"))
+                        } else if value.is_empty() {
+                            Err(anyhow!("Invalid entry: value is empty"))
+                        } else {
+                            self.add_entry(category, value, None)
+                                .with_context(|| "Failed to add entry")
                         }
-                    };
+                    }
+                    None => Err(anyhow!("Invalid entry")),
+                };
+
+                let reply = match res {
+                    Ok(_) => message.text_reply("Saved!"),
+                    Err(e) => message.text_reply(format!("Errored! {}", e)),
+                };
+
+                if let Err(e) = api.send(reply).await {
+                    eprintln!("Failed to send Telegram reply: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Handles a `/query` or `/stats` command already matched against the bot's command regex.
+    fn handle_telegram_command(&mut self, caps: &regex::Captures) -> String {
+        if let Err(e) = self.load() {
+            return format!("Errored! {}", e);
+        }
+
+        let command = caps.get(1).unwrap().as_str().to_lowercase();
+        let category = caps.get(2).unwrap().as_str();
+        let arg = caps.get(3).map(|m| m.as_str());
+
+        let reply_text = match command.as_str() {
+            "query" => {
+                let range = arg.and_then(|a| a.parse::<i64>().ok()).unwrap_or(7);
+                self.query_to_string(category, &QueryRange::Days(range))
+            }
+            "stats" => {
+                let range = arg.and_then(|a| a.parse::<i64>().ok());
+                self.stats_to_string(category, range)
+            }
+            _ => unreachable!("command_re only matches query|stats"),
+        };
+
+        if reply_text.trim().is_empty() {
+            "No results.".to_string()
+        } else {
+            reply_text
+        }
+    }
+
+    fn export(&self, format: Format, out: &Path) -> Result<()> {
+        match format {
+            Format::Plain => {
+                let mut f = File::create(out)?;
+                for entry in &self.entries {
+                    writeln!(f, "{}", entry)?;
+                }
+            }
+            Format::Json => {
+                let f = File::create(out)?;
+                serde_json::to_writer_pretty(f, &self.entries)?;
+            }
+            Format::Msgpack => {
+                let mut f = File::create(out)?;
+                f.write_all(&rmp_serde::to_vec(&self.entries)?)?;
+            }
+            Format::Csv => {
+                let mut wtr = csv::Writer::from_path(out)?;
+                for entry in &self.entries {
+                    wtr.serialize(EntryRecord::from(entry))?;
                 }
+                wtr.flush()?;
             }
         }
+
+        Ok(())
+    }
+
+    fn import(&mut self, format: Format, file: &Path) -> Result<()> {
+        let imported: Vec<Entry> = match format {
+            Format::Plain => {
+                let f = File::open(file)?;
+                let reader = BufReader::new(f);
+                let mut entries = vec![];
+                for line in reader.lines() {
+                    let l = line?;
+                    if l.is_empty() {
+                        continue;
+                    }
+                    entries.push(Entry::from(&l)?);
+                }
+                entries
+            }
+            Format::Json => {
+                let f = File::open(file)?;
+                let entries: Vec<Entry> =
+                    serde_json::from_reader(f).with_context(|| "Failed to parse JSON import file")?;
+                entries
+                    .into_iter()
+                    .map(revalidate_imported_entry)
+                    .collect::<Result<Vec<Entry>>>()?
+            }
+            Format::Msgpack => {
+                let bytes = std::fs::read(file)?;
+                let entries: Vec<Entry> = rmp_serde::from_slice(&bytes)
+                    .with_context(|| "Failed to parse Msgpack import file")?;
+                entries
+                    .into_iter()
+                    .map(revalidate_imported_entry)
+                    .collect::<Result<Vec<Entry>>>()?
+            }
+            Format::Csv => {
+                let mut rdr = csv::Reader::from_path(file)?;
+                let mut entries = vec![];
+                for result in rdr.deserialize() {
+                    let record: EntryRecord = result?;
+                    entries.push(revalidate_imported_entry(Entry::try_from(record)?)?);
+                }
+                entries
+            }
+        };
+
+        let file = OpenOptions::new().append(true).open(&self.track_file)?;
+        let mut writer = BufWriter::new(file);
+        for entry in &imported {
+            writeln!(writer, "{}", entry)?;
+        }
+        writer.flush()?;
+
+        self.entries.extend(imported);
+
         Ok(())
     }
+
+    async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let track = Arc::new(AsyncMutex::new(self));
+
+        let make_svc = make_service_fn(move |_conn| {
+            let track = track.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let track = track.clone();
+                    async move { Ok::<_, Infallible>(Track::route(track, req).await) }
+                }))
+            }
+        });
+
+        println!("Listening on http://{}", addr);
+        Server::bind(&addr)
+            .serve(make_svc)
+            .await
+            .with_context(|| "HTTP server error")
+    }
+
+    async fn route(track: Arc<AsyncMutex<Track>>, req: Request<Body>) -> Response<Body> {
+        match (req.method(), req.uri().path()) {
+            (&Method::POST, "/entries") => Track::handle_add_entry(track, req).await,
+            (&Method::GET, "/entries") => Track::handle_query_entries(track, req).await,
+            _ => Track::json_error(StatusCode::NOT_FOUND, "Not found"),
+        }
+    }
+
+    async fn handle_add_entry(track: Arc<AsyncMutex<Track>>, req: Request<Body>) -> Response<Body> {
+        let bytes = match hyper::body::to_bytes(req.into_body()).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                return Track::json_error(
+                    StatusCode::BAD_REQUEST,
+                    &format!("Failed to read request body: {}", e),
+                )
+            }
+        };
+
+        let payload: AddEntryRequest = match serde_json::from_slice(&bytes) {
+            Ok(payload) => payload,
+            Err(e) => {
+                return Track::json_error(StatusCode::BAD_REQUEST, &format!("Invalid JSON: {}", e))
+            }
+        };
+
+        let at = match payload.at.as_deref().map(parse_at).transpose() {
+            Ok(at) => at,
+            Err(e) => return Track::json_error(StatusCode::BAD_REQUEST, &e.to_string()),
+        };
+
+        if let Err(e) = validate_entry_fields(&payload.category, &payload.info) {
+            return Track::json_error(StatusCode::BAD_REQUEST, &e.to_string());
+        }
+
+        let track = track.lock().await;
+        match track.add_entry(&payload.category, &payload.info, at) {
+            Ok(()) => Track::json_body(StatusCode::CREATED, &serde_json::json!({"status": "saved"})),
+            Err(e) => Track::json_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+        }
+    }
+
+    async fn handle_query_entries(
+        track: Arc<AsyncMutex<Track>>,
+        req: Request<Body>,
+    ) -> Response<Body> {
+        let params = parse_query_params(req.uri().query().unwrap_or(""));
+
+        let category = match params.get("category") {
+            Some(category) => category.clone(),
+            None => {
+                return Track::json_error(
+                    StatusCode::BAD_REQUEST,
+                    "Missing 'category' query parameter",
+                )
+            }
+        };
+        let range: i64 = match params.get("range") {
+            Some(range) => match range.parse() {
+                Ok(range) => range,
+                Err(_) => {
+                    return Track::json_error(
+                        StatusCode::BAD_REQUEST,
+                        &format!("Invalid 'range' query parameter: '{}'", range),
+                    )
+                }
+            },
+            None => 7,
+        };
+
+        let mut track = track.lock().await;
+        if let Err(e) = track.load() {
+            return Track::json_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string());
+        }
+
+        let now: Date<Local> = Local::now().date();
+        let min_date = now
+            .checked_sub_signed(chrono::Duration::days(range))
+            .unwrap();
+        let entries: Vec<&Entry> = track
+            .entries
+            .iter()
+            .filter(|e| e.categories.contains(&category) && e.date.date() > min_date)
+            .collect();
+        let aggregate = Entry::aggregate(entries);
+
+        Track::json_body(StatusCode::OK, &aggregate)
+    }
+
+    fn json_body<T: Serialize>(status: StatusCode, value: &T) -> Response<Body> {
+        match serde_json::to_vec(value) {
+            Ok(body) => Response::builder()
+                .status(status)
+                .header("Content-Type", "application/json")
+                .body(Body::from(body))
+                .unwrap(),
+            Err(e) => Track::json_error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+        }
+    }
+
+    fn json_error(status: StatusCode, message: &str) -> Response<Body> {
+        Track::json_body(status, &ApiError {
+            error: message.to_string(),
+        })
+    }
+
+    /// Plain-text recap of today's entries by category, or `None` if empty.
+    fn daily_summary(&self) -> Option<String> {
+        let today = Local::now().date();
+        let mut out = String::new();
+        let mut any = false;
+
+        for (category, entries) in self
+            .entries
+            .iter()
+            .filter(|e| e.date.date() == today)
+            .sorted_by(|e1, e2| e1.categories.cmp(&e2.categories))
+            .group_by(|e| e.categories.to_string())
+            .into_iter()
+        {
+            any = true;
+            let agg = Entry::aggregate(entries.collect::<Vec<&Entry>>());
+            for (log, count) in &agg.logs {
+                writeln!(
+                    out,
+                    "{}: {}{}",
+                    category,
+                    log,
+                    if *count != 1 {
+                        format!(" x{}", count)
+                    } else {
+                        String::new()
+                    }
+                )
+                .unwrap();
+            }
+            for (unit, total) in &agg.quantities {
+                writeln!(out, "{}: {}{}", category, total, unit).unwrap();
+            }
+        }
+
+        if any {
+            Some(format!("Daily summary for {}:\n{}", today.format("%d %b %Y"), out))
+        } else {
+            None
+        }
+    }
+
+    async fn notify(&mut self, dry_run: bool) -> Result<()> {
+        self.load()?;
+
+        let summary = match self.daily_summary() {
+            Some(summary) => summary,
+            None => {
+                println!("No entries tracked today; nothing to notify.");
+                return Ok(());
+            }
+        };
+
+        let urls = env::var("TRACK_WEBHOOK_URLS")
+            .with_context(|| "TRACK_WEBHOOK_URLS not set")?;
+        let urls: Vec<&str> = urls
+            .split(',')
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .collect();
+        if urls.is_empty() {
+            bail!("TRACK_WEBHOOK_URLS is set but contains no URLs");
+        }
+
+        let client = reqwest::Client::new();
+        let total = urls.len();
+        let mut failures = vec![];
+        for url in urls {
+            let sink = sink_for_url(url);
+            let payload = sink.payload(&summary);
+
+            if dry_run {
+                println!("{}:\n{}", url, serde_json::to_string_pretty(&payload)?);
+                continue;
+            }
+
+            if let Err(e) = client.post(url).json(&payload).send().await {
+                failures.push(format!("{}: {}", url, e));
+            }
+        }
+
+        if !failures.is_empty() {
+            bail!(
+                "Failed to notify {} of {} webhook(s):\n{}",
+                failures.len(),
+                total,
+                failures.join("\n")
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Turns a plain-text summary into a webhook's expected JSON body.
+trait NotifySink {
+    fn payload(&self, summary: &str) -> serde_json::Value;
+}
+
+struct SlackSink;
+
+impl NotifySink for SlackSink {
+    fn payload(&self, summary: &str) -> serde_json::Value {
+        serde_json::json!({ "text": summary })
+    }
+}
+
+struct DiscordSink;
+
+impl NotifySink for DiscordSink {
+    fn payload(&self, summary: &str) -> serde_json::Value {
+        serde_json::json!({ "content": summary })
+    }
+}
+
+fn sink_for_url(url: &str) -> Box<dyn NotifySink> {
+    if url.contains("discord.com") {
+        Box::new(DiscordSink)
+    } else {
+        Box::new(SlackSink)
+    }
+}
+
+/// Minimal `key=value&...` query-string split (no percent-decoding).
+fn parse_query_params(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|kv| !kv.is_empty())
+        .map(|kv| {
+            let mut parts = kv.splitn(2, '=');
+            let key = parts.next().unwrap_or("").to_string();
+            let value = parts.next().unwrap_or("").to_string();
+            (key, value)
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct AddEntryRequest {
+    category: String,
+    info: String,
+    at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiError {
+    error: String,
 }
 
-#[derive(Debug)]
+mod rfc3339_date {
+    use chrono::{DateTime, Local};
+    use serde::{self, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(date: &DateTime<Local>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&date.to_rfc3339())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Local>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&s)
+            .map(|dt| dt.with_timezone(&Local))
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 struct Entry {
+    #[serde(with = "rfc3339_date")]
     date: DateTime<Local>,
     categories: String,
     info: EntryInfo,
@@ -315,7 +1040,8 @@ impl Entry {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
 enum EntryInfo {
     Q(Quantity),
     L(String),
@@ -347,7 +1073,7 @@ impl fmt::Display for EntryInfo {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct Quantity {
     quantity: f32,
     unit: String,
@@ -359,7 +1085,125 @@ impl fmt::Display for Quantity {
     }
 }
 
+#[derive(Serialize)]
 struct EntryInfoAggregate {
     logs: HashMap<String, i64>,
     quantities: HashMap<String, f32>,
 }
+
+/// Mean of the per-ISO-week totals of the (at most) 4 latest weeks that have entries;
+/// weeks without entries are skipped, so those weeks need not be consecutive.
+fn weekly_moving_average(values: &[(NaiveDate, f32)]) -> f32 {
+    let mut weekly: HashMap<(i32, u32), f32> = HashMap::new();
+    for (date, quantity) in values {
+        let iso = date.iso_week();
+        *weekly.entry((iso.year(), iso.week())).or_insert(0.0) += quantity;
+    }
+
+    let mut weeks: Vec<&(i32, u32)> = weekly.keys().collect();
+    weeks.sort();
+
+    const WINDOW: usize = 4;
+    let window = weeks.len().min(WINDOW);
+    if window == 0 {
+        return 0.0;
+    }
+    let recent_sum: f32 = weeks[weeks.len() - window..]
+        .iter()
+        .map(|week| weekly[*week])
+        .sum();
+
+    recent_sum / window as f32
+}
+
+/// Returns `(current_streak, longest_streak)` in consecutive days logged.
+fn compute_streaks(dates: &std::collections::BTreeSet<NaiveDate>) -> (i64, i64) {
+    let sorted: Vec<NaiveDate> = dates.iter().cloned().collect();
+    if sorted.is_empty() {
+        return (0, 0);
+    }
+
+    let mut longest = 1i64;
+    let mut run_start = 0usize;
+    for i in 1..sorted.len() {
+        if (sorted[i] - sorted[i - 1]).num_days() > 1 {
+            run_start = i;
+        }
+        longest = longest.max((i - run_start + 1) as i64);
+    }
+
+    let today = Local::now().date().naive_local();
+    let yesterday = today - chrono::Duration::days(1);
+    let last = *sorted.last().unwrap();
+    let current = if last == today || last == yesterday {
+        let mut len = 1i64;
+        let mut i = sorted.len() - 1;
+        while i > 0 && (sorted[i] - sorted[i - 1]).num_days() == 1 {
+            len += 1;
+            i -= 1;
+        }
+        len
+    } else {
+        0
+    };
+
+    (current, longest)
+}
+
+/// Flat row layout used for CSV export/import.
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryRecord {
+    date: String,
+    categories: String,
+    info: String,
+}
+
+impl From<&Entry> for EntryRecord {
+    fn from(entry: &Entry) -> Self {
+        EntryRecord {
+            date: entry.date.to_rfc3339(),
+            categories: entry.categories.clone(),
+            info: entry.info.to_string(),
+        }
+    }
+}
+
+impl TryFrom<EntryRecord> for Entry {
+    type Error = anyhow::Error;
+
+    fn try_from(record: EntryRecord) -> Result<Entry> {
+        let date = DateTime::parse_from_rfc3339(&record.date)?.with_timezone(&Local);
+        let info = EntryInfo::from(&record.info)?;
+        Ok(Entry {
+            date,
+            categories: record.categories,
+            info,
+        })
+    }
+}
+
+/// Rejects categories/info that would corrupt the `[date] categories:info` line format.
+fn validate_entry_fields(categories: &str, info: &str) -> Result<()> {
+    if categories.contains(':') || categories.contains("] ") || categories.contains('\n') {
+        bail!(
+            "Invalid categories '{}': must not contain ':', '] ' or newlines",
+            categories
+        );
+    }
+    if info.contains(':') || info.contains('\n') {
+        bail!("Invalid info '{}': must not contain ':' or newlines", info);
+    }
+    Ok(())
+}
+
+/// Re-derives an imported entry's `info` via `EntryInfo::from` and lowercases its `categories`.
+fn revalidate_imported_entry(entry: Entry) -> Result<Entry> {
+    let raw_info = entry.info.to_string();
+    validate_entry_fields(&entry.categories, &raw_info)?;
+    let info = EntryInfo::from(&raw_info)?;
+    Ok(Entry {
+        categories: entry.categories.to_lowercase(),
+        info,
+        ..entry
+    })
+}